@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use tauri::{AppHandle, Manager};
+
+pub const DB_FILE: &str = "lifeline.db";
+
+/// Path to the `lifeline.db` file inside the app's data directory.
+pub fn db_path(app: &AppHandle) -> PathBuf {
+  app
+    .path()
+    .app_data_dir()
+    .expect("app data dir should be resolvable")
+    .join(DB_FILE)
+}
+
+/// Opens a pool onto the same `lifeline.db` file that `tauri-plugin-sql` manages for the
+/// frontend. Used by the handful of commands that need direct Rust-side access to the
+/// database (migrations, content hashing, signing) rather than issuing SQL from JS.
+///
+/// Creates the app data dir and the database file itself if they don't exist yet, since
+/// this can run ahead of the frontend's own `Database.load()` call on first launch.
+pub async fn connect(app: &AppHandle) -> sqlx::Result<SqlitePool> {
+  let path = db_path(app);
+  if let Some(parent) = path.parent() {
+    tokio::fs::create_dir_all(parent).await.map_err(sqlx::Error::Io)?;
+  }
+
+  let options = SqliteConnectOptions::new()
+    .filename(&path)
+    .create_if_missing(true);
+
+  SqlitePoolOptions::new()
+    .max_connections(1)
+    .connect_with(options)
+    .await
+}
@@ -0,0 +1,121 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::db;
+
+/// Migration adding a bounding-box index for geotagged items.
+pub(crate) const UP_SQL: &str = UP_5;
+
+const UP_5: &str = r#"
+  CREATE INDEX IF NOT EXISTS idx_items_place ON items(place_lat, place_lng);
+"#;
+
+const DOWN_5: &str = r#"
+  DROP INDEX IF EXISTS idx_items_place;
+"#;
+
+pub(crate) const DOWN_SQL: &str = DOWN_5;
+
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PlaceCluster {
+  Single {
+    item_id: String,
+    caption: Option<String>,
+    lat: f64,
+    lng: f64,
+  },
+  Cluster {
+    count: i64,
+    lat: f64,
+    lng: f64,
+  },
+}
+
+/// Grid cell size, in degrees, for a given zoom level: halves every time `zoom`
+/// increases by one, so higher zoom means finer clustering.
+fn cell_size_degrees(zoom: i32) -> f64 {
+  360.0_f64 / 2f64.powi(zoom)
+}
+
+/// Buckets geotagged items within the given bounding box into a uniform grid whose cell
+/// size shrinks as `zoom` increases (cell degrees = `360 / 2^zoom`), grouping items by
+/// `(floor(lat/cell), floor(lng/cell))`. Cells with a single item are returned directly
+/// (with id and caption) so the frontend can render a real marker; cells with more than
+/// one item are returned as an aggregate with a count and centroid for the frontend to
+/// zoom into.
+#[tauri::command]
+pub async fn cluster_places(
+  app: AppHandle,
+  min_lat: f64,
+  min_lng: f64,
+  max_lat: f64,
+  max_lng: f64,
+  zoom: i32,
+) -> Result<Vec<PlaceCluster>, String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+  let cell = cell_size_degrees(zoom);
+
+  let rows = sqlx::query_as::<_, (i64, String, Option<String>, f64, f64)>(
+    r#"
+      SELECT COUNT(*) AS cnt,
+             MIN(id) AS sample_id,
+             MIN(caption) AS sample_caption,
+             AVG(place_lat) AS centroid_lat,
+             AVG(place_lng) AS centroid_lng
+      FROM items
+      WHERE place_lat IS NOT NULL AND place_lng IS NOT NULL
+        AND place_lat BETWEEN ? AND ?
+        AND place_lng BETWEEN ? AND ?
+      GROUP BY CAST(FLOOR(place_lat / ?) AS INTEGER), CAST(FLOOR(place_lng / ?) AS INTEGER)
+    "#,
+  )
+  .bind(min_lat)
+  .bind(max_lat)
+  .bind(min_lng)
+  .bind(max_lng)
+  .bind(cell)
+  .bind(cell)
+  .fetch_all(&pool)
+  .await
+  .map_err(|e| e.to_string())?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|(count, sample_id, sample_caption, lat, lng)| {
+        if count == 1 {
+          PlaceCluster::Single {
+            item_id: sample_id,
+            caption: sample_caption,
+            lat,
+            lng,
+          }
+        } else {
+          PlaceCluster::Cluster { count, lat, lng }
+        }
+      })
+      .collect(),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cell_size_halves_per_zoom_level() {
+    assert_eq!(cell_size_degrees(0), 360.0);
+    assert_eq!(cell_size_degrees(1), 180.0);
+    assert_eq!(cell_size_degrees(8), 360.0 / 256.0);
+  }
+
+  #[test]
+  fn bucket_matches_for_points_in_the_same_cell() {
+    let cell = cell_size_degrees(8);
+    let bucket = |lat: f64, lng: f64| ((lat / cell).floor() as i64, (lng / cell).floor() as i64);
+
+    assert_eq!(bucket(38.70, -9.15), bucket(38.71, -9.14));
+    assert_ne!(bucket(38.70, -9.15), bucket(51.50, -0.12));
+  }
+}
@@ -0,0 +1,288 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+
+/// Migration adding the content-addressed `media` table.
+pub(crate) const UP_SQL: &str = UP_3;
+
+const UP_3: &str = r#"
+  CREATE TABLE IF NOT EXISTS media (
+    id TEXT PRIMARY KEY,
+    content_hash TEXT NOT NULL UNIQUE,
+    mime TEXT NOT NULL,
+    width INTEGER,
+    height INTEGER,
+    byte_size INTEGER NOT NULL,
+    original_path TEXT NOT NULL,
+    thumb_path TEXT,
+    created_at TEXT NOT NULL
+  );
+  CREATE INDEX IF NOT EXISTS idx_media_content_hash ON media(content_hash);
+"#;
+
+const DOWN_3: &str = r#"
+  DROP INDEX IF EXISTS idx_media_content_hash;
+  DROP TABLE IF EXISTS media;
+"#;
+
+pub(crate) const DOWN_SQL: &str = DOWN_3;
+
+const THUMB_MAX_DIM: u32 = 320;
+
+#[derive(Serialize)]
+pub struct MediaAsset {
+  pub id: String,
+  pub content_hash: String,
+  pub mime: String,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  pub byte_size: i64,
+  pub original_path: String,
+  pub thumb_path: Option<String>,
+  pub created_at: String,
+}
+
+/// Imports a media file at `path`, deduplicating on its BLAKE3 content hash.
+///
+/// If a `media` row with the same hash already exists, its id is returned and no bytes
+/// are copied again. Otherwise the file is copied into the app data dir under a
+/// hash-sharded path (first two hex chars as a subdirectory) and, for formats the
+/// `image` crate can decode, a downscaled JPEG thumbnail is generated alongside it for
+/// fast timeline rendering. Formats it can't decode (videos, for instance) are stored
+/// with `thumb_path: None` rather than a path to a file that was never written.
+///
+/// The existence check below is only a fast path to skip file work for the common case;
+/// it doesn't by itself prevent two concurrent imports of the same new file from both
+/// missing it and racing to insert. The final `INSERT OR IGNORE` + re-`SELECT` is what
+/// actually makes that race safe: whichever caller's row wins the `content_hash` unique
+/// constraint is the one every caller ends up returning, sequential or not.
+#[tauri::command]
+pub async fn import_media(app: AppHandle, path: String) -> Result<MediaAsset, String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+  let source = PathBuf::from(&path);
+
+  let bytes = tokio::fs::read(&source).await.map_err(|e| e.to_string())?;
+  let content_hash = blake3::hash(&bytes).to_hex().to_string();
+
+  if let Some(existing) = sqlx::query_as::<_, MediaRow>(
+    "SELECT id, content_hash, mime, width, height, byte_size, original_path, thumb_path, created_at
+     FROM media WHERE content_hash = ?",
+  )
+  .bind(&content_hash)
+  .fetch_optional(&pool)
+  .await
+  .map_err(|e| e.to_string())?
+  {
+    return Ok(existing.into());
+  }
+
+  let media_dir = media_dir(&app)?;
+  let shard = &content_hash[..2];
+  let shard_dir = media_dir.join(shard);
+  tokio::fs::create_dir_all(&shard_dir)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let extension = source
+    .extension()
+    .and_then(|e| e.to_str())
+    .unwrap_or("bin");
+  let dest = shard_dir.join(format!("{content_hash}.{extension}"));
+  if !dest.exists() {
+    tokio::fs::write(&dest, &bytes)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+
+  let mime = mime_guess::from_path(&source)
+    .first_or_octet_stream()
+    .to_string();
+  let image = image::load_from_memory(&bytes).ok();
+  let (width, height) = image
+    .as_ref()
+    .map(|img| (img.width(), img.height()))
+    .unzip();
+
+  let thumb_path = if let Some(img) = &image {
+    let thumb_path = shard_dir.join(format!("{content_hash}_thumb.jpg"));
+    let thumb = img.thumbnail(THUMB_MAX_DIM, THUMB_MAX_DIM);
+    thumb
+      .to_rgb8()
+      .save_with_format(&thumb_path, image::ImageFormat::Jpeg)
+      .map_err(|e| e.to_string())?;
+    Some(thumb_path.to_string_lossy().to_string())
+  } else {
+    None
+  };
+
+  let id = uuid::Uuid::new_v4().to_string();
+  let created_at = time::OffsetDateTime::now_utc()
+    .format(&time::format_description::well_known::Rfc3339)
+    .map_err(|e| e.to_string())?;
+  let byte_size = bytes.len() as i64;
+  let original_path = dest.to_string_lossy().to_string();
+
+  let row = upsert_media_row(
+    &pool,
+    &id,
+    &content_hash,
+    &mime,
+    width,
+    height,
+    byte_size,
+    &original_path,
+    thumb_path.as_deref(),
+    &created_at,
+  )
+  .await
+  .map_err(|e| e.to_string())?;
+
+  Ok(row.into())
+}
+
+/// Inserts `id`'s row unless a row for `content_hash` already exists, then always
+/// re-reads by `content_hash` and returns that row.
+///
+/// Split out of [`import_media`] so two racing imports of the same new file — both
+/// having missed the existence check above, both building their own candidate id and
+/// row — resolve to whichever one's `INSERT OR IGNORE` actually won the `content_hash`
+/// unique constraint, rather than the second one erroring out on it.
+#[allow(clippy::too_many_arguments)]
+async fn upsert_media_row(
+  pool: &sqlx::SqlitePool,
+  id: &str,
+  content_hash: &str,
+  mime: &str,
+  width: Option<u32>,
+  height: Option<u32>,
+  byte_size: i64,
+  original_path: &str,
+  thumb_path: Option<&str>,
+  created_at: &str,
+) -> sqlx::Result<MediaRow> {
+  sqlx::query(
+    "INSERT OR IGNORE INTO media (id, content_hash, mime, width, height, byte_size, original_path, thumb_path, created_at)
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(id)
+  .bind(content_hash)
+  .bind(mime)
+  .bind(width)
+  .bind(height)
+  .bind(byte_size)
+  .bind(original_path)
+  .bind(thumb_path)
+  .bind(created_at)
+  .execute(pool)
+  .await?;
+
+  // `content_hash` is UNIQUE, so if another concurrent import of the same bytes won the
+  // race, our own INSERT above was silently ignored. Re-reading by hash rather than
+  // trusting the row we just built means every racing caller returns the exact same
+  // winning row, whether or not it was the one that wrote it.
+  sqlx::query_as::<_, MediaRow>(
+    "SELECT id, content_hash, mime, width, height, byte_size, original_path, thumb_path, created_at
+     FROM media WHERE content_hash = ?",
+  )
+  .bind(content_hash)
+  .fetch_one(pool)
+  .await
+}
+
+fn media_dir(app: &AppHandle) -> Result<PathBuf, String> {
+  let dir = app
+    .path()
+    .app_data_dir()
+    .map_err(|e| e.to_string())?
+    .join("media");
+  Ok(dir)
+}
+
+#[derive(sqlx::FromRow)]
+struct MediaRow {
+  id: String,
+  content_hash: String,
+  mime: String,
+  width: Option<u32>,
+  height: Option<u32>,
+  byte_size: i64,
+  original_path: String,
+  thumb_path: Option<String>,
+  created_at: String,
+}
+
+impl From<MediaRow> for MediaAsset {
+  fn from(row: MediaRow) -> Self {
+    MediaAsset {
+      id: row.id,
+      content_hash: row.content_hash,
+      mime: row.mime,
+      width: row.width,
+      height: row.height,
+      byte_size: row.byte_size,
+      original_path: row.original_path,
+      thumb_path: row.thumb_path,
+      created_at: row.created_at,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn memory_pool_with_schema() -> sqlx::SqlitePool {
+    let pool = sqlx::SqlitePool::connect("sqlite::memory:")
+      .await
+      .expect("in-memory sqlite connection");
+    sqlx::raw_sql(UP_SQL).execute(&pool).await.expect("media schema");
+    pool
+  }
+
+  #[tokio::test]
+  async fn concurrent_imports_of_the_same_hash_resolve_to_one_row() {
+    let pool = memory_pool_with_schema().await;
+
+    let first = upsert_media_row(
+      &pool,
+      "id-a",
+      "same-hash",
+      "image/jpeg",
+      Some(100),
+      Some(100),
+      1024,
+      "/media/aa/same-hash.jpg",
+      None,
+      "2024-01-01T00:00:00Z",
+    );
+    let second = upsert_media_row(
+      &pool,
+      "id-b",
+      "same-hash",
+      "image/jpeg",
+      Some(100),
+      Some(100),
+      1024,
+      "/media/aa/same-hash.jpg",
+      None,
+      "2024-01-01T00:00:01Z",
+    );
+
+    let (first, second) = tokio::join!(first, second);
+    let first = first.expect("first import resolves");
+    let second = second.expect("second import resolves");
+
+    assert_eq!(first.id, second.id, "both callers should see the same winning row");
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM media WHERE content_hash = ?")
+      .bind("same-hash")
+      .fetch_one(&pool)
+      .await
+      .expect("count rows");
+    assert_eq!(count, 1, "only one row should have been written");
+  }
+}
+
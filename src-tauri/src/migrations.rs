@@ -0,0 +1,218 @@
+use tauri::AppHandle;
+
+use crate::db;
+use crate::geo;
+use crate::media;
+use crate::nostr;
+use crate::search;
+use crate::tags;
+
+/// The newest schema version this build knows about. `run_pending` replays every `Up`
+/// migration between the database's recorded version and this one; `db_rollback` can
+/// undo any version down to 0.
+const LATEST_VERSION: i64 = 6;
+
+/// Bootstraps `_migration_meta` itself. Runs unconditionally, ahead of everything else,
+/// since we need it to exist before we can even ask what version the database is at.
+const BOOTSTRAP_SQL: &str = r#"
+  CREATE TABLE IF NOT EXISTS _migration_meta (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    version INTEGER NOT NULL
+  );
+  INSERT OR IGNORE INTO _migration_meta (id, version) VALUES (0, 0);
+"#;
+
+/// The base schema (version 1), exposed so other modules' tests can stand up an
+/// in-memory database with `events`/`items` already in place without going through the
+/// full migration runner.
+#[cfg(test)]
+pub(crate) const CORE_SCHEMA_SQL: &str = UP_1;
+
+const UP_1: &str = r#"
+  CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    type TEXT NOT NULL CHECK(type IN ('year', 'period', 'event', 'item')),
+    title TEXT,
+    start_at TEXT NOT NULL,
+    end_at TEXT,
+    parent_id TEXT REFERENCES events(id),
+    cover_media_id TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+  );
+  CREATE TABLE IF NOT EXISTS items (
+    id TEXT PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES events(id),
+    item_type TEXT NOT NULL CHECK(item_type IN ('text', 'photo', 'video', 'link')),
+    content TEXT NOT NULL,
+    caption TEXT,
+    happened_at TEXT,
+    place_lat REAL,
+    place_lng REAL,
+    place_label TEXT
+  );
+  CREATE TABLE IF NOT EXISTS canvas_items (
+    event_id TEXT NOT NULL REFERENCES events(id),
+    item_id TEXT NOT NULL REFERENCES items(id),
+    x REAL NOT NULL DEFAULT 0,
+    y REAL NOT NULL DEFAULT 0,
+    scale REAL NOT NULL DEFAULT 1,
+    rotation REAL NOT NULL DEFAULT 0,
+    z_index INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (event_id, item_id)
+  );
+  CREATE INDEX IF NOT EXISTS idx_events_parent ON events(parent_id);
+  CREATE INDEX IF NOT EXISTS idx_events_start ON events(start_at);
+  CREATE INDEX IF NOT EXISTS idx_items_event ON items(event_id);
+"#;
+
+const DOWN_1: &str = r#"
+  DROP INDEX IF EXISTS idx_items_event;
+  DROP INDEX IF EXISTS idx_events_start;
+  DROP INDEX IF EXISTS idx_events_parent;
+  DROP TABLE IF EXISTS canvas_items;
+  DROP TABLE IF EXISTS items;
+  DROP TABLE IF EXISTS events;
+"#;
+
+/// Returns the `Up` SQL for the given schema version, if we have one on file.
+fn up_sql(version: i64) -> Option<&'static str> {
+  match version {
+    1 => Some(UP_1),
+    2 => Some(search::UP_SQL),
+    3 => Some(media::UP_SQL),
+    4 => Some(tags::UP_SQL),
+    5 => Some(geo::UP_SQL),
+    6 => Some(nostr::UP_SQL),
+    _ => None,
+  }
+}
+
+/// Returns the `Down` SQL that undoes the given schema version, if we have one on file.
+///
+/// Every subsystem that adds a migration registers its version in both `up_sql` and
+/// `down_sql`, so `run_pending`/`db_rollback` have a single place to look regardless of
+/// which module owns the schema.
+fn down_sql(version: i64) -> Option<&'static str> {
+  match version {
+    1 => Some(DOWN_1),
+    2 => Some(search::DOWN_SQL),
+    3 => Some(media::DOWN_SQL),
+    4 => Some(tags::DOWN_SQL),
+    5 => Some(geo::DOWN_SQL),
+    6 => Some(nostr::DOWN_SQL),
+    _ => None,
+  }
+}
+
+/// Applies every `Up` migration between the database's recorded version and
+/// [`LATEST_VERSION`], in ascending order, inside a single transaction.
+///
+/// We drive the whole migration lifecycle ourselves — both this and [`db_rollback`] read
+/// and write `_migration_meta` directly — rather than relying on `tauri-plugin-sql`'s own
+/// migration runner, since that runner only applies migrations when the frontend calls
+/// `Database.load()` and has no way to know about versions `db_rollback` has undone.
+/// Called from `setup()`, before the webview (and therefore any frontend `Database.load()`
+/// or command invocation) can see the database, so the schema is never queried half-built.
+pub async fn run_pending(app: &AppHandle) -> Result<(), String> {
+  let pool = db::connect(app).await.map_err(|e| e.to_string())?;
+
+  sqlx::raw_sql(BOOTSTRAP_SQL)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let current_version: i64 =
+    sqlx::query_scalar("SELECT version FROM _migration_meta WHERE id = 0")
+      .fetch_one(&pool)
+      .await
+      .map_err(|e| e.to_string())?;
+
+  if current_version >= LATEST_VERSION {
+    return Ok(());
+  }
+
+  let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+  for version in (current_version + 1)..=LATEST_VERSION {
+    let sql =
+      up_sql(version).ok_or_else(|| format!("no up migration recorded for version {version}"))?;
+    sqlx::raw_sql(sql)
+      .execute(&mut *tx)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+
+  sqlx::query("UPDATE _migration_meta SET version = ? WHERE id = 0")
+    .bind(LATEST_VERSION)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tx.commit().await.map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+/// Rolls `sqlite:lifeline.db` back to `target_version` by applying the recorded `Down`
+/// scripts for every version above it, in descending order, inside a single transaction.
+/// Any failure aborts the whole rollback and leaves the database untouched. A
+/// `target_version` at or above the currently applied version is a no-op. Since `run_pending` is the only
+/// thing that ever applies `Up` migrations, the next launch after a rollback will replay
+/// exactly the versions this command undid.
+#[tauri::command]
+pub async fn db_rollback(app: AppHandle, target_version: i64) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  let current_version: i64 =
+    sqlx::query_scalar("SELECT version FROM _migration_meta WHERE id = 0")
+      .fetch_one(&pool)
+      .await
+      .map_err(|e| e.to_string())?;
+
+  if target_version >= current_version {
+    return Ok(());
+  }
+
+  let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+  for version in (target_version + 1..=current_version).rev() {
+    let sql = down_sql(version)
+      .ok_or_else(|| format!("no down migration recorded for version {version}"))?;
+    sqlx::raw_sql(sql)
+      .execute(&mut *tx)
+      .await
+      .map_err(|e| e.to_string())?;
+  }
+
+  sqlx::query("UPDATE _migration_meta SET version = ? WHERE id = 0")
+    .bind(target_version)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tx.commit().await.map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn up_and_down_sql_cover_every_version_through_latest() {
+    for version in 1..=LATEST_VERSION {
+      assert!(
+        up_sql(version).is_some(),
+        "missing up migration for version {version}"
+      );
+      assert!(
+        down_sql(version).is_some(),
+        "missing down migration for version {version}"
+      );
+    }
+    assert!(up_sql(LATEST_VERSION + 1).is_none());
+    assert!(down_sql(LATEST_VERSION + 1).is_none());
+  }
+}
@@ -0,0 +1,378 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::db;
+
+/// Custom Nostr event kind used to carry an event plus its items as one signed note.
+/// Parameterized-replaceable (30000-39999 range) so re-publishing the same event
+/// replaces the prior note instead of accumulating duplicates on relays.
+const TIMELINE_EVENT_KIND: u16 = 30078;
+
+/// Migration adding the `identity` table holding the locally-generated signing key
+/// used to publish and authenticate synced timeline events.
+pub(crate) const UP_SQL: &str = UP_6;
+
+const UP_6: &str = r#"
+  CREATE TABLE IF NOT EXISTS identity (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    secret_key TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TEXT NOT NULL
+  );
+"#;
+
+const DOWN_6: &str = r#"
+  DROP TABLE IF EXISTS identity;
+"#;
+
+pub(crate) const DOWN_SQL: &str = DOWN_6;
+
+/// The local signing identity used for Nostr sync. The key is generated on first launch
+/// (from `setup()`, right after the migrations) and persisted in `identity` so it
+/// survives restarts.
+///
+/// This state starts empty and is filled in lazily by the first command that needs it,
+/// which loads the persisted key and builds the client.
+///
+/// The `Client` is cached alongside the keys and reused across `publish_event`/
+/// `pull_events` calls instead of being recreated per call: `nostr_sdk::Client` owns a
+/// live websocket per relay, and those connections are only closed by dropping (or
+/// explicitly disconnecting) the client, so a fresh one per call would accumulate
+/// connections for the app's lifetime.
+#[derive(Default)]
+pub struct NostrState(tokio::sync::Mutex<Option<(nostr_sdk::Keys, nostr_sdk::Client)>>);
+
+/// Returns the cached signing key and client, loading the key from `identity`
+/// (generating and persisting one on first use) and creating the client the first time
+/// any command needs them. Any `relays` not already added to the cached client are added
+/// and connected before returning.
+async fn get_or_init_client(
+  app: &AppHandle,
+  state: &NostrState,
+  relays: &[String],
+) -> Result<nostr_sdk::Client, String> {
+  let mut cached = state.0.lock().await;
+  if cached.is_none() {
+    let keys = load_or_create_identity(app).await?;
+    *cached = Some((keys.clone(), nostr_sdk::Client::new(keys)));
+  }
+
+  let (_, client) = cached.as_ref().expect("just initialized above");
+  for relay in relays {
+    client.add_relay(relay).await.map_err(|e| e.to_string())?;
+  }
+  client.connect().await;
+
+  Ok(client.clone())
+}
+
+/// Loads the signing key from `identity`, generating and persisting a new one on first
+/// launch.
+pub(crate) async fn load_or_create_identity(app: &AppHandle) -> Result<nostr_sdk::Keys, String> {
+  let pool = db::connect(app).await.map_err(|e| e.to_string())?;
+
+  if let Some((secret_key,)) =
+    sqlx::query_as::<_, (String,)>("SELECT secret_key FROM identity WHERE id = 0")
+      .fetch_optional(&pool)
+      .await
+      .map_err(|e| e.to_string())?
+  {
+    return nostr_sdk::Keys::parse(&secret_key).map_err(|e| e.to_string());
+  }
+
+  let keys = nostr_sdk::Keys::generate();
+  let created_at = time::OffsetDateTime::now_utc()
+    .format(&time::format_description::well_known::Rfc3339)
+    .map_err(|e| e.to_string())?;
+
+  sqlx::query("INSERT INTO identity (id, secret_key, public_key, created_at) VALUES (0, ?, ?, ?)")
+    .bind(keys.secret_key().to_secret_hex())
+    .bind(keys.public_key().to_hex())
+    .bind(&created_at)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(keys)
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimelineItem {
+  id: String,
+  item_type: String,
+  content: String,
+  caption: Option<String>,
+  happened_at: Option<String>,
+  place_lat: Option<f64>,
+  place_lng: Option<f64>,
+  place_label: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimelineNote {
+  event: TimelineEventPayload,
+  items: Vec<TimelineItem>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TimelineEventPayload {
+  id: String,
+  r#type: String,
+  title: Option<String>,
+  start_at: String,
+  end_at: Option<String>,
+  parent_id: Option<String>,
+}
+
+/// Publishes `event_id` (and its items) to `relays` as a signed, replaceable Nostr note,
+/// so the same event re-published later overwrites the prior copy instead of
+/// accumulating duplicates.
+#[tauri::command]
+pub async fn publish_event(
+  app: AppHandle,
+  state: tauri::State<'_, NostrState>,
+  event_id: String,
+  relays: Vec<String>,
+) -> Result<String, String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  let event_row = sqlx::query_as::<_, (String, String, Option<String>, String, Option<String>, Option<String>)>(
+    "SELECT id, type, title, start_at, end_at, parent_id FROM events WHERE id = ?",
+  )
+  .bind(&event_id)
+  .fetch_one(&pool)
+  .await
+  .map_err(|e| e.to_string())?;
+
+  let item_rows = sqlx::query_as::<_, (String, String, String, Option<String>, Option<String>, Option<f64>, Option<f64>, Option<String>)>(
+    "SELECT id, item_type, content, caption, happened_at, place_lat, place_lng, place_label FROM items WHERE event_id = ?",
+  )
+  .bind(&event_id)
+  .fetch_all(&pool)
+  .await
+  .map_err(|e| e.to_string())?;
+
+  let note = TimelineNote {
+    event: TimelineEventPayload {
+      id: event_row.0,
+      r#type: event_row.1,
+      title: event_row.2,
+      start_at: event_row.3,
+      end_at: event_row.4,
+      parent_id: event_row.5,
+    },
+    items: item_rows
+      .into_iter()
+      .map(
+        |(id, item_type, content, caption, happened_at, place_lat, place_lng, place_label)| {
+          TimelineItem {
+            id,
+            item_type,
+            content,
+            caption,
+            happened_at,
+            place_lat,
+            place_lng,
+            place_label,
+          }
+        },
+      )
+      .collect(),
+  };
+
+  let content = serde_json::to_string(&note).map_err(|e| e.to_string())?;
+
+  let client = get_or_init_client(&app, &state, &relays).await?;
+
+  let builder = nostr_sdk::EventBuilder::new(
+    nostr_sdk::Kind::Custom(TIMELINE_EVENT_KIND),
+    content,
+  )
+  .tag(nostr_sdk::Tag::identifier(event_id.clone()));
+
+  let output = client.send_event_builder(builder).await.map_err(|e| e.to_string())?;
+
+  Ok(output.id().to_hex())
+}
+
+/// Pulls every timeline note published by `pubkey` from `relays`, checks that each note's
+/// embedded pubkey actually matches the requested `author` (a relay could otherwise hand
+/// back validly-signed notes from an unrelated key under this filter) and that its
+/// signature verifies, then upserts the decoded events/items into the local tables keyed
+/// by id so re-syncing the same notes is idempotent.
+#[tauri::command]
+pub async fn pull_events(
+  app: AppHandle,
+  state: tauri::State<'_, NostrState>,
+  pubkey: String,
+  relays: Vec<String>,
+) -> Result<usize, String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+  let author = nostr_sdk::PublicKey::parse(&pubkey).map_err(|e| e.to_string())?;
+
+  let client = get_or_init_client(&app, &state, &relays).await?;
+
+  let filter = nostr_sdk::Filter::new()
+    .author(author)
+    .kind(nostr_sdk::Kind::Custom(TIMELINE_EVENT_KIND));
+
+  let events = client
+    .fetch_events(filter, std::time::Duration::from_secs(10))
+    .await
+    .map_err(|e| e.to_string())?;
+
+  let mut imported = 0;
+  for nostr_event in events.into_iter() {
+    if nostr_event.pubkey != author {
+      continue;
+    }
+    if nostr_event.verify().is_err() {
+      continue;
+    }
+
+    let note: TimelineNote = match serde_json::from_str(&nostr_event.content) {
+      Ok(note) => note,
+      Err(_) => continue,
+    };
+
+    upsert_note(&pool, &note, &nostr_event.created_at.to_string())
+      .await
+      .map_err(|e| e.to_string())?;
+    imported += 1;
+  }
+
+  Ok(imported)
+}
+
+/// Upserts a decoded [`TimelineNote`] (and its items) into the local `events`/`items`
+/// tables, keyed by id, inside one transaction. `received_at` is only used to seed
+/// `events.created_at` the first time an event is seen; re-upserting the same note
+/// afterwards is a no-op beyond refreshing the mutable columns, which is what makes
+/// re-pulling the same relay notes safe to do repeatedly.
+async fn upsert_note(
+  pool: &sqlx::SqlitePool,
+  note: &TimelineNote,
+  received_at: &str,
+) -> sqlx::Result<()> {
+  let mut tx = pool.begin().await?;
+
+  sqlx::query(
+    "INSERT INTO events (id, type, title, start_at, end_at, parent_id, created_at, updated_at)
+     VALUES (?, ?, ?, ?, ?, ?, COALESCE((SELECT created_at FROM events WHERE id = ?), ?), ?)
+     ON CONFLICT(id) DO UPDATE SET
+       type = excluded.type,
+       title = excluded.title,
+       start_at = excluded.start_at,
+       end_at = excluded.end_at,
+       parent_id = excluded.parent_id,
+       updated_at = excluded.updated_at",
+  )
+  .bind(&note.event.id)
+  .bind(&note.event.r#type)
+  .bind(&note.event.title)
+  .bind(&note.event.start_at)
+  .bind(&note.event.end_at)
+  .bind(&note.event.parent_id)
+  .bind(&note.event.id)
+  .bind(received_at)
+  .bind(received_at)
+  .execute(&mut *tx)
+  .await?;
+
+  for item in &note.items {
+    sqlx::query(
+      "INSERT INTO items (id, event_id, item_type, content, caption, happened_at, place_lat, place_lng, place_label)
+       VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+       ON CONFLICT(id) DO UPDATE SET
+         item_type = excluded.item_type,
+         content = excluded.content,
+         caption = excluded.caption,
+         happened_at = excluded.happened_at,
+         place_lat = excluded.place_lat,
+         place_lng = excluded.place_lng,
+         place_label = excluded.place_label",
+    )
+    .bind(&item.id)
+    .bind(&note.event.id)
+    .bind(&item.item_type)
+    .bind(&item.content)
+    .bind(&item.caption)
+    .bind(&item.happened_at)
+    .bind(item.place_lat)
+    .bind(item.place_lng)
+    .bind(&item.place_label)
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tx.commit().await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn memory_pool_with_schema() -> sqlx::SqlitePool {
+    let pool = sqlx::SqlitePool::connect("sqlite::memory:")
+      .await
+      .expect("in-memory sqlite connection");
+    sqlx::raw_sql(crate::migrations::CORE_SCHEMA_SQL)
+      .execute(&pool)
+      .await
+      .expect("core schema");
+    pool
+  }
+
+  fn sample_note() -> TimelineNote {
+    TimelineNote {
+      event: TimelineEventPayload {
+        id: "ev1".into(),
+        r#type: "event".into(),
+        title: Some("Lisbon trip".into()),
+        start_at: "2024-01-01".into(),
+        end_at: None,
+        parent_id: None,
+      },
+      items: vec![TimelineItem {
+        id: "it1".into(),
+        item_type: "text".into(),
+        content: "arrived".into(),
+        caption: None,
+        happened_at: None,
+        place_lat: None,
+        place_lng: None,
+        place_label: None,
+      }],
+    }
+  }
+
+  #[tokio::test]
+  async fn pulling_the_same_note_twice_is_idempotent() {
+    let pool = memory_pool_with_schema().await;
+    let note = sample_note();
+
+    upsert_note(&pool, &note, "100").await.expect("first upsert");
+    upsert_note(&pool, &note, "200").await.expect("second upsert");
+
+    let event_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE id = 'ev1'")
+      .fetch_one(&pool)
+      .await
+      .expect("count events");
+    let item_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items WHERE id = 'it1'")
+      .fetch_one(&pool)
+      .await
+      .expect("count items");
+    assert_eq!(event_count, 1, "re-pulling should update, not duplicate, the event");
+    assert_eq!(item_count, 1, "re-pulling should update, not duplicate, the item");
+
+    let created_at: String =
+      sqlx::query_scalar("SELECT created_at FROM events WHERE id = 'ev1'")
+        .fetch_one(&pool)
+        .await
+        .expect("fetch created_at");
+    assert_eq!(
+      created_at, "100",
+      "created_at should be seeded from the first sync and left alone by the second"
+    );
+  }
+}
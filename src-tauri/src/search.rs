@@ -0,0 +1,205 @@
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::db;
+
+/// Migration adding full-text search over `events.title` and
+/// `items.content`/`caption`/`place_label`.
+///
+/// The FTS5 table stores its own copy of the indexed text plus the owning
+/// `event_id`/`item_id` (so `snippet()` and the id columns can be read back), and is kept
+/// in sync by triggers on the base tables so callers never have to remember to update
+/// the index themselves.
+pub(crate) const UP_SQL: &str = UP_2;
+
+const UP_2: &str = r#"
+  CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    event_id UNINDEXED,
+    item_id UNINDEXED,
+    text
+  );
+
+  -- events.title
+  CREATE TRIGGER IF NOT EXISTS trg_search_events_ai AFTER INSERT ON events WHEN new.title IS NOT NULL BEGIN
+    INSERT INTO search_index(rowid, event_id, item_id, text) VALUES (new.rowid, new.id, NULL, new.title);
+  END;
+  CREATE TRIGGER IF NOT EXISTS trg_search_events_au AFTER UPDATE ON events BEGIN
+    DELETE FROM search_index WHERE rowid = old.rowid AND item_id IS NULL;
+    INSERT INTO search_index(rowid, event_id, item_id, text)
+      SELECT new.rowid, new.id, NULL, new.title WHERE new.title IS NOT NULL;
+  END;
+  CREATE TRIGGER IF NOT EXISTS trg_search_events_ad AFTER DELETE ON events BEGIN
+    DELETE FROM search_index WHERE rowid = old.rowid AND item_id IS NULL;
+  END;
+
+  -- items.content / caption / place_label, combined into one row per item
+  CREATE TRIGGER IF NOT EXISTS trg_search_items_ai AFTER INSERT ON items BEGIN
+    INSERT INTO search_index(rowid, event_id, item_id, text)
+      VALUES (
+        new.rowid + 1000000000,
+        new.event_id,
+        new.id,
+        trim(coalesce(new.content, '') || ' ' || coalesce(new.caption, '') || ' ' || coalesce(new.place_label, ''))
+      );
+  END;
+  CREATE TRIGGER IF NOT EXISTS trg_search_items_au AFTER UPDATE ON items BEGIN
+    DELETE FROM search_index WHERE rowid = old.rowid + 1000000000;
+    INSERT INTO search_index(rowid, event_id, item_id, text)
+      VALUES (
+        new.rowid + 1000000000,
+        new.event_id,
+        new.id,
+        trim(coalesce(new.content, '') || ' ' || coalesce(new.caption, '') || ' ' || coalesce(new.place_label, ''))
+      );
+  END;
+  CREATE TRIGGER IF NOT EXISTS trg_search_items_ad AFTER DELETE ON items BEGIN
+    DELETE FROM search_index WHERE rowid = old.rowid + 1000000000;
+  END;
+
+  -- backfill rows that existed before this migration ran
+  INSERT INTO search_index(rowid, event_id, item_id, text)
+    SELECT rowid, id, NULL, title FROM events WHERE title IS NOT NULL;
+  INSERT INTO search_index(rowid, event_id, item_id, text)
+    SELECT rowid + 1000000000, event_id, id, trim(coalesce(content, '') || ' ' || coalesce(caption, '') || ' ' || coalesce(place_label, ''))
+    FROM items;
+"#;
+
+const DOWN_2: &str = r#"
+  DROP TRIGGER IF EXISTS trg_search_items_ad;
+  DROP TRIGGER IF EXISTS trg_search_items_au;
+  DROP TRIGGER IF EXISTS trg_search_items_ai;
+  DROP TRIGGER IF EXISTS trg_search_events_ad;
+  DROP TRIGGER IF EXISTS trg_search_events_au;
+  DROP TRIGGER IF EXISTS trg_search_events_ai;
+  DROP TABLE IF EXISTS search_index;
+"#;
+
+pub(crate) const DOWN_SQL: &str = DOWN_2;
+
+#[derive(Serialize)]
+pub struct SearchHit {
+  pub event_id: String,
+  pub item_id: Option<String>,
+  pub snippet: String,
+  pub rank: f64,
+}
+
+/// Full-text search over event titles and item content/caption/place, ranked by
+/// `bm25()`. `query` is passed straight through to FTS5, so prefix queries like
+/// `lisb*` work as-is.
+#[tauri::command]
+pub async fn search(app: AppHandle, query: String, limit: i64) -> Result<Vec<SearchHit>, String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+  run_search(&pool, &query, limit).await.map_err(|e| e.to_string())
+}
+
+/// Does the actual querying against an already-connected pool, split out from
+/// [`search`] so it can run against an in-memory database in tests without a real
+/// `AppHandle`.
+async fn run_search(
+  pool: &sqlx::SqlitePool,
+  query: &str,
+  limit: i64,
+) -> sqlx::Result<Vec<SearchHit>> {
+  sqlx::query_as::<_, (String, Option<String>, String, f64)>(
+    r#"
+      SELECT
+        event_id,
+        item_id,
+        snippet(search_index, 2, '[', ']', '…', 10) AS snippet,
+        bm25(search_index) AS rank
+      FROM search_index
+      WHERE search_index MATCH ?
+      ORDER BY rank
+      LIMIT ?
+    "#,
+  )
+  .bind(query)
+  .bind(limit)
+  .fetch_all(pool)
+  .await
+  .map(|rows| {
+    rows
+      .into_iter()
+      .map(|(event_id, item_id, snippet, rank)| SearchHit {
+        event_id,
+        item_id,
+        snippet,
+        rank,
+      })
+      .collect()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn memory_pool_with_schema() -> sqlx::SqlitePool {
+    let pool = sqlx::SqlitePool::connect("sqlite::memory:")
+      .await
+      .expect("in-memory sqlite connection");
+    sqlx::raw_sql(crate::migrations::CORE_SCHEMA_SQL)
+      .execute(&pool)
+      .await
+      .expect("core schema");
+    sqlx::raw_sql(UP_SQL).execute(&pool).await.expect("search schema");
+    pool
+  }
+
+  #[tokio::test]
+  async fn search_finds_and_ranks_a_freshly_inserted_item() {
+    let pool = memory_pool_with_schema().await;
+
+    sqlx::query(
+      "INSERT INTO events (id, type, title, start_at, created_at, updated_at)
+       VALUES ('ev1', 'event', 'Lisbon trip', '2024-01-01', '2024-01-01', '2024-01-01')",
+    )
+    .execute(&pool)
+    .await
+    .expect("insert event");
+
+    sqlx::query(
+      "INSERT INTO items (id, event_id, item_type, content, caption)
+       VALUES ('it1', 'ev1', 'text', 'walked along the river in Lisbon', 'river walk')",
+    )
+    .execute(&pool)
+    .await
+    .expect("insert item");
+
+    let hits = run_search(&pool, "Lisbon", 10).await.expect("search");
+
+    assert_eq!(hits.len(), 2, "expects the event title hit and the item hit");
+    assert!(hits.iter().any(|h| h.event_id == "ev1" && h.item_id.is_none()));
+    let item_hit = hits
+      .iter()
+      .find(|h| h.item_id.as_deref() == Some("it1"))
+      .expect("item hit present");
+    assert!(item_hit.snippet.contains('[') && item_hit.snippet.contains(']'));
+  }
+
+  #[tokio::test]
+  async fn search_respects_prefix_queries_and_limit() {
+    let pool = memory_pool_with_schema().await;
+
+    sqlx::query(
+      "INSERT INTO events (id, type, title, start_at, created_at, updated_at)
+       VALUES ('ev1', 'event', 'Lisbon trip', '2024-01-01', '2024-01-01', '2024-01-01')",
+    )
+    .execute(&pool)
+    .await
+    .expect("insert event");
+
+    sqlx::query(
+      "INSERT INTO events (id, type, title, start_at, created_at, updated_at)
+       VALUES ('ev2', 'event', 'Listening party', '2024-02-01', '2024-02-01', '2024-02-01')",
+    )
+    .execute(&pool)
+    .await
+    .expect("insert event");
+
+    let hits = run_search(&pool, "lis*", 1).await.expect("search");
+
+    assert_eq!(hits.len(), 1, "limit should cap the result set");
+  }
+}
@@ -0,0 +1,343 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::db;
+
+/// Migration adding `tags` plus the `event_tags`/`item_tags` join tables.
+pub(crate) const UP_SQL: &str = UP_4;
+
+const UP_4: &str = r#"
+  CREATE TABLE IF NOT EXISTS tags (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    color TEXT
+  );
+  CREATE TABLE IF NOT EXISTS event_tags (
+    event_id TEXT NOT NULL REFERENCES events(id),
+    tag_id TEXT NOT NULL REFERENCES tags(id),
+    PRIMARY KEY (event_id, tag_id)
+  );
+  CREATE TABLE IF NOT EXISTS item_tags (
+    item_id TEXT NOT NULL REFERENCES items(id),
+    tag_id TEXT NOT NULL REFERENCES tags(id),
+    PRIMARY KEY (item_id, tag_id)
+  );
+  CREATE INDEX IF NOT EXISTS idx_event_tags_tag ON event_tags(tag_id);
+  CREATE INDEX IF NOT EXISTS idx_item_tags_tag ON item_tags(tag_id);
+"#;
+
+const DOWN_4: &str = r#"
+  DROP INDEX IF EXISTS idx_item_tags_tag;
+  DROP INDEX IF EXISTS idx_event_tags_tag;
+  DROP TABLE IF EXISTS item_tags;
+  DROP TABLE IF EXISTS event_tags;
+  DROP TABLE IF EXISTS tags;
+"#;
+
+pub(crate) const DOWN_SQL: &str = DOWN_4;
+
+/// Tag match semantics for [`events_by_tags`]/[`items_by_tags`]: `All` requires every
+/// given tag to be present (logical AND), `Any` requires at least one (logical OR).
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMatch {
+  All,
+  Any,
+}
+
+#[derive(Serialize)]
+pub struct Tag {
+  pub id: String,
+  pub name: String,
+  pub color: Option<String>,
+}
+
+#[tauri::command]
+pub async fn create_tag(app: AppHandle, name: String, color: Option<String>) -> Result<Tag, String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+  let id = uuid::Uuid::new_v4().to_string();
+
+  sqlx::query("INSERT INTO tags (id, name, color) VALUES (?, ?, ?)")
+    .bind(&id)
+    .bind(&name)
+    .bind(&color)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(Tag { id, name, color })
+}
+
+#[tauri::command]
+pub async fn rename_tag(app: AppHandle, tag_id: String, name: String) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  sqlx::query("UPDATE tags SET name = ? WHERE id = ?")
+    .bind(&name)
+    .bind(&tag_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_tag(app: AppHandle, tag_id: String) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+  let mut tx = pool.begin().await.map_err(|e| e.to_string())?;
+
+  sqlx::query("DELETE FROM event_tags WHERE tag_id = ?")
+    .bind(&tag_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+  sqlx::query("DELETE FROM item_tags WHERE tag_id = ?")
+    .bind(&tag_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+  sqlx::query("DELETE FROM tags WHERE id = ?")
+    .bind(&tag_id)
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  tx.commit().await.map_err(|e| e.to_string())?;
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn attach_event_tag(app: AppHandle, event_id: String, tag_id: String) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  sqlx::query("INSERT OR IGNORE INTO event_tags (event_id, tag_id) VALUES (?, ?)")
+    .bind(&event_id)
+    .bind(&tag_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn detach_event_tag(app: AppHandle, event_id: String, tag_id: String) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  sqlx::query("DELETE FROM event_tags WHERE event_id = ? AND tag_id = ?")
+    .bind(&event_id)
+    .bind(&tag_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn attach_item_tag(app: AppHandle, item_id: String, tag_id: String) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  sqlx::query("INSERT OR IGNORE INTO item_tags (item_id, tag_id) VALUES (?, ?)")
+    .bind(&item_id)
+    .bind(&tag_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+#[tauri::command]
+pub async fn detach_item_tag(app: AppHandle, item_id: String, tag_id: String) -> Result<(), String> {
+  let pool = db::connect(&app).await.map_err(|e| e.to_string())?;
+
+  sqlx::query("DELETE FROM item_tags WHERE item_id = ? AND tag_id = ?")
+    .bind(&item_id)
+    .bind(&tag_id)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+  Ok(())
+}
+
+/// Returns the ids of every event carrying all (or any, per `match_mode`) of `tag_ids`.
+#[tauri::command]
+pub async fn events_by_tags(
+  app: AppHandle,
+  tag_ids: Vec<String>,
+  match_mode: TagMatch,
+) -> Result<Vec<String>, String> {
+  by_tags(&app, "event_tags", "event_id", tag_ids, match_mode).await
+}
+
+/// Returns the ids of every item carrying all (or any, per `match_mode`) of `tag_ids`.
+#[tauri::command]
+pub async fn items_by_tags(
+  app: AppHandle,
+  tag_ids: Vec<String>,
+  match_mode: TagMatch,
+) -> Result<Vec<String>, String> {
+  by_tags(&app, "item_tags", "item_id", tag_ids, match_mode).await
+}
+
+async fn by_tags(
+  app: &AppHandle,
+  join_table: &str,
+  subject_column: &str,
+  tag_ids: Vec<String>,
+  match_mode: TagMatch,
+) -> Result<Vec<String>, String> {
+  let pool = db::connect(app).await.map_err(|e| e.to_string())?;
+  by_tags_in_pool(&pool, join_table, subject_column, tag_ids, match_mode)
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Does the actual querying against an already-connected pool, split out of [`by_tags`]
+/// so it can run against an in-memory database in tests without a real `AppHandle`.
+async fn by_tags_in_pool(
+  pool: &sqlx::SqlitePool,
+  join_table: &str,
+  subject_column: &str,
+  tag_ids: Vec<String>,
+  match_mode: TagMatch,
+) -> sqlx::Result<Vec<String>> {
+  if tag_ids.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  let placeholders = tag_ids.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+
+  let sql = match match_mode {
+    TagMatch::Any => format!(
+      "SELECT DISTINCT {subject_column} FROM {join_table} WHERE tag_id IN ({placeholders})"
+    ),
+    TagMatch::All => {
+      // `COUNT(DISTINCT tag_id)` never exceeds the number of *distinct* tags requested,
+      // so comparing it against the raw `tag_ids.len()` would undercount (and return
+      // nothing) whenever the caller passes a duplicate id.
+      let distinct_tag_count = tag_ids.iter().collect::<HashSet<_>>().len();
+      format!(
+        "SELECT {subject_column} FROM {join_table} WHERE tag_id IN ({placeholders})
+         GROUP BY {subject_column} HAVING COUNT(DISTINCT tag_id) = {distinct_tag_count}"
+      )
+    }
+  };
+
+  let mut query = sqlx::query_scalar::<_, String>(&sql);
+  for tag_id in &tag_ids {
+    query = query.bind(tag_id);
+  }
+
+  query.fetch_all(pool).await
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  async fn memory_pool_with_schema() -> sqlx::SqlitePool {
+    let pool = sqlx::SqlitePool::connect("sqlite::memory:")
+      .await
+      .expect("in-memory sqlite connection");
+    sqlx::raw_sql(crate::migrations::CORE_SCHEMA_SQL)
+      .execute(&pool)
+      .await
+      .expect("core schema");
+    sqlx::raw_sql(UP_SQL).execute(&pool).await.expect("tags schema");
+    pool
+  }
+
+  async fn seed_event_with_tags(pool: &sqlx::SqlitePool, event_id: &str, tag_ids: &[&str]) {
+    sqlx::query(
+      "INSERT INTO events (id, type, start_at, created_at, updated_at)
+       VALUES (?, 'event', '2024-01-01', '2024-01-01', '2024-01-01')",
+    )
+    .bind(event_id)
+    .execute(pool)
+    .await
+    .expect("insert event");
+
+    for tag_id in tag_ids {
+      sqlx::query("INSERT OR IGNORE INTO tags (id, name) VALUES (?, ?)")
+        .bind(tag_id)
+        .bind(tag_id)
+        .execute(pool)
+        .await
+        .expect("insert tag");
+      sqlx::query("INSERT INTO event_tags (event_id, tag_id) VALUES (?, ?)")
+        .bind(event_id)
+        .bind(tag_id)
+        .execute(pool)
+        .await
+        .expect("insert event_tag");
+    }
+  }
+
+  #[tokio::test]
+  async fn all_match_requires_every_distinct_tag() {
+    let pool = memory_pool_with_schema().await;
+    seed_event_with_tags(&pool, "ev-both", &["t1", "t2"]).await;
+    seed_event_with_tags(&pool, "ev-one", &["t1"]).await;
+
+    let matches = by_tags_in_pool(
+      &pool,
+      "event_tags",
+      "event_id",
+      vec!["t1".into(), "t2".into()],
+      TagMatch::All,
+    )
+    .await
+    .expect("query");
+
+    assert_eq!(matches, vec!["ev-both".to_string()]);
+  }
+
+  #[tokio::test]
+  async fn all_match_is_unaffected_by_duplicate_tag_ids() {
+    let pool = memory_pool_with_schema().await;
+    seed_event_with_tags(&pool, "ev-one", &["t1"]).await;
+
+    let matches = by_tags_in_pool(
+      &pool,
+      "event_tags",
+      "event_id",
+      vec!["t1".into(), "t1".into()],
+      TagMatch::All,
+    )
+    .await
+    .expect("query");
+
+    assert_eq!(
+      matches,
+      vec!["ev-one".to_string()],
+      "a duplicated tag id in the request shouldn't make an otherwise-matching event disappear"
+    );
+  }
+
+  #[tokio::test]
+  async fn any_match_requires_only_one_tag() {
+    let pool = memory_pool_with_schema().await;
+    seed_event_with_tags(&pool, "ev-both", &["t1", "t2"]).await;
+    seed_event_with_tags(&pool, "ev-one", &["t1"]).await;
+    seed_event_with_tags(&pool, "ev-none", &[]).await;
+
+    let mut matches = by_tags_in_pool(
+      &pool,
+      "event_tags",
+      "event_id",
+      vec!["t1".into()],
+      TagMatch::Any,
+    )
+    .await
+    .expect("query");
+    matches.sort();
+
+    assert_eq!(matches, vec!["ev-both".to_string(), "ev-one".to_string()]);
+  }
+}